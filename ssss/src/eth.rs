@@ -1,19 +1,23 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, VecDeque},
+    fmt::Debug,
     sync::Arc,
     time::{Duration, Instant},
 };
 
+use async_trait::async_trait;
 use ethers::{
     abi::AbiDecode,
     contract::EthLogDecode as _,
     providers::{
-        Http, HttpRateLimitRetryPolicy, JsonRpcClient as _, Middleware, Provider as EthersProvider,
-        Quorum, QuorumProvider, RetryClient, WeightedProvider,
+        Http, HttpRateLimitRetryPolicy, JsonRpcClient as _, Middleware, PubsubClient as _,
+        Provider as EthersProvider, ProviderError, Quorum, QuorumProvider, RetryClient,
+        WeightedProvider, Ws,
     },
-    types::{Address, Filter, Log, Transaction, TxHash, ValueOrArray, H256, U64},
+    types::{Address, Block, Filter, Log, Transaction, TxHash, ValueOrArray, H256, U64},
 };
 use futures::{future::BoxFuture, FutureExt, Stream, StreamExt as _, TryStreamExt as _};
+use serde::{de::DeserializeOwned, Serialize};
 use smallvec::{smallvec, SmallVec};
 use tokio::sync::{Mutex, OnceCell};
 use tracing::{trace, warn};
@@ -23,8 +27,108 @@ use crate::{
     utils::{retry, retry_if},
 };
 
-pub type Providers = HashMap<ChainId, Provider>;
-pub type Provider = EthersProvider<Arc<QuorumProvider<RetryClient<Http>>>>;
+pub type Providers = HashMap<ChainId, AnyProvider>;
+pub type Provider = EthersProvider<Arc<HttpClient>>;
+pub type WsProvider = EthersProvider<Ws>;
+
+/// Which `ethers` consensus policy a chain's HTTP endpoints must satisfy
+/// before a response is accepted. Mirrors [`Quorum`] so callers can build a
+/// [`ProvidersConfig`] without depending on `ethers` themselves.
+#[derive(Clone, Copy, Debug, Default)]
+pub enum QuorumPolicy {
+    #[default]
+    Majority,
+    All,
+    ProviderCount(usize),
+    Weight(u64),
+}
+
+impl From<QuorumPolicy> for Quorum {
+    fn from(policy: QuorumPolicy) -> Self {
+        match policy {
+            QuorumPolicy::Majority => Quorum::Majority,
+            QuorumPolicy::All => Quorum::All,
+            QuorumPolicy::ProviderCount(n) => Quorum::ProviderCount(n),
+            QuorumPolicy::Weight(w) => Quorum::Weight(w),
+        }
+    }
+}
+
+/// How a chain's HTTP endpoints are combined into its [`Provider`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ProvidersConfig {
+    pub quorum: QuorumPolicy,
+}
+
+/// `RetryClient` backoff tuning for one endpoint, previously hard-coded to
+/// `10` retries with a `2_000`ms initial backoff for every endpoint
+/// regardless of its rate limits.
+#[derive(Clone, Copy, Debug)]
+pub struct RetryConfig {
+    pub max_retries: u32,
+    pub initial_backoff_ms: u64,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 10,
+            initial_backoff_ms: 2_000,
+        }
+    }
+}
+
+/// One HTTP RPC endpoint, along with its role in its chain's provider pool.
+///
+/// Marking an endpoint `primary` takes it out of the `QuorumProvider` pool
+/// entirely: every request goes to it alone, and the other endpoints for
+/// that chain — combined into their own `QuorumProvider` under the chain's
+/// [`QuorumPolicy`] — are only ever queried once the primary errors. See
+/// [`quorum_provider`]. `weight` is ignored for a primary endpoint.
+#[derive(Clone, Debug)]
+pub struct EndpointConfig {
+    pub rpc: String,
+    pub weight: u64,
+    pub primary: bool,
+    pub retry: RetryConfig,
+}
+
+impl EndpointConfig {
+    pub fn new(rpc: impl Into<String>) -> Self {
+        Self {
+            rpc: rpc.into(),
+            weight: 1,
+            primary: false,
+            retry: RetryConfig::default(),
+        }
+    }
+
+    pub fn with_weight(mut self, weight: u64) -> Self {
+        self.weight = weight;
+        self
+    }
+
+    pub fn as_primary(mut self) -> Self {
+        self.primary = true;
+        self
+    }
+
+    pub fn with_retry(mut self, retry: RetryConfig) -> Self {
+        self.retry = retry;
+        self
+    }
+}
+
+/// A connection to a chain's RPC, either HTTP-polled or WebSocket-pushed.
+///
+/// The two transports expose different block/event delivery strategies (see
+/// [`SsssPermitter::events`] vs [`SsssPermitter::events_subscribed`]), so callers
+/// match on this instead of treating every endpoint as pollable.
+#[derive(Clone)]
+pub enum AnyProvider {
+    Http(Provider),
+    Ws(WsProvider),
+}
 
 ethers::contract::abigen!(
     SsssPermitterContract,
@@ -40,6 +144,91 @@ ethers::contract::abigen!(
     ]"
 );
 
+/// How many recent block hashes to retain for reorg detection. Must be at
+/// least as deep as the largest `confirmations` any chain is configured
+/// with, or a reorg below that depth can't be traced back to its fork point.
+const PARENT_HASH_WINDOW: usize = 256;
+
+/// The parent-hash chain of the most recently processed blocks, used to spot
+/// reorgs even when a provider doesn't mark reverted logs as `removed`.
+#[derive(Default)]
+struct ParentHashes(VecDeque<(u64, H256)>);
+
+impl ParentHashes {
+    fn hash_at(&self, height: u64) -> Option<H256> {
+        self.0.iter().find(|(h, _)| *h == height).map(|(_, h)| *h)
+    }
+
+    fn record(&mut self, height: u64, hash: H256) {
+        self.0.retain(|(h, _)| *h != height);
+        self.0.push_back((height, hash));
+        while self.0.len() > PARENT_HASH_WINDOW {
+            self.0.pop_front();
+        }
+    }
+
+    fn forget_from(&mut self, height: u64) {
+        self.0.retain(|(h, _)| *h < height);
+    }
+}
+
+/// A step of [`SsssPermitter::blocks`]/[`SsssPermitter::blocks_subscribed`]:
+/// either the next block to process, or a reorg whose abandoned range
+/// (everything from the given height onward) must be rolled back.
+enum BlockStep {
+    Block(u64),
+    Reorg(u64),
+}
+
+#[cfg(test)]
+mod parent_hashes_tests {
+    use super::*;
+
+    fn h(n: u8) -> H256 {
+        H256::from_low_u64_be(n as u64)
+    }
+
+    #[test]
+    fn hash_at_finds_recorded_height() {
+        let mut hashes = ParentHashes::default();
+        hashes.record(10, h(1));
+        hashes.record(11, h(2));
+        assert_eq!(hashes.hash_at(10), Some(h(1)));
+        assert_eq!(hashes.hash_at(11), Some(h(2)));
+        assert_eq!(hashes.hash_at(12), None);
+    }
+
+    #[test]
+    fn record_overwrites_existing_height() {
+        let mut hashes = ParentHashes::default();
+        hashes.record(10, h(1));
+        hashes.record(10, h(2));
+        assert_eq!(hashes.hash_at(10), Some(h(2)));
+    }
+
+    #[test]
+    fn record_evicts_oldest_beyond_window() {
+        let mut hashes = ParentHashes::default();
+        for height in 0..(PARENT_HASH_WINDOW as u64 + 1) {
+            hashes.record(height, h(0));
+        }
+        assert_eq!(hashes.hash_at(0), None);
+        assert_eq!(hashes.hash_at(PARENT_HASH_WINDOW as u64), Some(h(0)));
+    }
+
+    #[test]
+    fn forget_from_drops_height_and_above_only() {
+        let mut hashes = ParentHashes::default();
+        hashes.record(10, h(1));
+        hashes.record(11, h(2));
+        hashes.record(12, h(3));
+        hashes.forget_from(11);
+        assert_eq!(hashes.hash_at(10), Some(h(1)));
+        assert_eq!(hashes.hash_at(11), None);
+        assert_eq!(hashes.hash_at(12), None);
+    }
+}
+
 #[derive(Clone)]
 pub struct SsssPermitter<M> {
     pub chain: u64,
@@ -47,20 +236,32 @@ pub struct SsssPermitter<M> {
     contract: SsssPermitterContract<M>,
     provider: Arc<M>,
 
+    /// How many blocks behind head are treated as final. Chains with slower
+    /// or less final consensus (e.g. frequent single-block reorgs) should
+    /// set this higher.
+    confirmations: u64,
+
     creation_block: Arc<OnceCell<u64>>,
     upstream: Arc<Mutex<(Address, Instant)>>,
+    parent_hashes: Arc<Mutex<ParentHashes>>,
 }
 
 impl<M: Middleware> SsssPermitter<M> {
     pub fn new(chain: u64, address: Address, provider: M) -> Self {
+        Self::with_confirmations(chain, address, provider, 0)
+    }
+
+    pub fn with_confirmations(chain: u64, address: Address, provider: M, confirmations: u64) -> Self {
         let provider = Arc::new(provider);
         Self {
             chain,
             address,
             contract: SsssPermitterContract::new(address, provider.clone()),
             provider,
+            confirmations,
             creation_block: Default::default(),
             upstream: Arc::new(Mutex::new((Address::zero(), Instant::now()))),
+            parent_hashes: Default::default(),
         }
     }
 
@@ -100,46 +301,109 @@ impl<M: Middleware> SsssPermitter<M> {
         Ok(tx.transaction_hash)
     }
 
+    /// Grants the given identities to their requesters for `durations` in a
+    /// single `approveRequests` call, batching what would otherwise be one
+    /// transaction per verified permit request.
+    pub async fn approve_requests(
+        &self,
+        identities: Vec<IdentityId>,
+        requesters: Vec<Address>,
+        durations: Vec<u64>,
+    ) -> Result<TxHash, Error<M>> {
+        let tx = self
+            .contract
+            .approve_requests(
+                identities.into_iter().map(|id| id.0.into()).collect(),
+                requesters,
+                durations,
+            )
+            .send()
+            .await?
+            .await?
+            .unwrap();
+        Ok(tx.transaction_hash)
+    }
+
     pub fn events(
         &self,
         start_block: u64,
         stop_block: Option<u64>,
     ) -> impl Stream<Item = BoxFuture<SmallVec<[Event; 4]>>> {
         async_stream::stream!({
-            for await block in self.blocks(start_block).await {
-                yield self.get_block_events(block, self.address).boxed();
-                yield futures::future::ready(smallvec![Event {
-                    kind: EventKind::ProcessedBlock,
-                    index: Default::default(),
-                    tx: Default::default(),
-                }]).boxed();
-                if Some(block) == stop_block {
-                    break;
+            for await step in self.blocks(start_block).await {
+                match step {
+                    BlockStep::Block(block) => {
+                        yield self.get_block_events(block, self.address).boxed();
+                        yield futures::future::ready(smallvec![Event {
+                            kind: EventKind::ProcessedBlock,
+                            index: EventIndex { block, log_index: 0 },
+                            tx: Default::default(),
+                        }]).boxed();
+                        if Some(block) == stop_block {
+                            break;
+                        }
+                    }
+                    BlockStep::Reorg(rescan_from) => {
+                        yield Self::reverted_from(rescan_from);
+                    }
                 }
             }
         })
     }
 
-    async fn blocks(&self, start_block: u64) -> impl Stream<Item = u64> + '_ {
+    /// A [`Event::kind`] of [`EventKind::Reverted`] anchored at `height`,
+    /// telling the store to undo whatever was derived from events at or
+    /// after `height` — used when [`Self::check_for_reorg`] finds a reorg
+    /// the provider didn't flag with `removed: true` on its own, since
+    /// otherwise the abandoned fork's state (e.g. a stale verifier policy
+    /// from a `Configuration` event that only existed on the orphaned
+    /// chain) would never get rolled back.
+    fn reverted_from(height: u64) -> BoxFuture<'static, SmallVec<[Event; 4]>> {
+        let index = EventIndex {
+            block: height,
+            log_index: 0,
+        };
+        futures::future::ready(smallvec![Event {
+            kind: EventKind::Reverted { index },
+            tx: None,
+            index,
+        }])
+        .boxed()
+    }
+
+    async fn blocks(&self, start_block: u64) -> impl Stream<Item = BlockStep> + '_ {
         let init_block = retry(|| async {
             Ok::<_, Error<M>>(
                 self.provider
                     .get_block_number()
                     .await
-                    .map_err(Error::RpcProvider)?
+                    .map_err(|e| {
+                        crate::metrics::record_rpc_error(self.chain);
+                        Error::RpcProvider(e)
+                    })?
                     .as_u64(),
             )
         })
         .await;
+        crate::metrics::observe_head(self.chain, init_block);
+        let init_block = init_block.saturating_sub(self.confirmations);
         async_stream::stream!({
             let mut current_block = start_block;
             loop {
-                if current_block <= init_block {
-                    yield current_block;
-                } else {
+                if current_block > init_block {
                     self.wait_for_block(current_block).await;
-                    yield current_block;
                 }
+                if let Some(rescan_from) = self.check_for_reorg(current_block).await {
+                    warn!(
+                        from = rescan_from,
+                        to = current_block,
+                        "reorg detected; rescanning from divergence point"
+                    );
+                    yield BlockStep::Reorg(rescan_from);
+                    current_block = rescan_from;
+                    continue;
+                }
+                yield BlockStep::Block(current_block);
                 current_block += 1;
             }
         })
@@ -147,29 +411,89 @@ impl<M: Middleware> SsssPermitter<M> {
 
     async fn wait_for_block(&self, block_number: u64) {
         trace!(block = block_number, "waiting for block");
-        retry_if(
+        let confirmations = self.confirmations;
+        let head = retry_if(
             || async {
                 Ok::<_, Error<M>>(
                     self.provider
                         .get_block_number()
                         .await
-                        .map_err(Error::RpcProvider)?
+                        .map_err(|e| {
+                            crate::metrics::record_rpc_error(self.chain);
+                            Error::RpcProvider(e)
+                        })?
                         .as_u64(),
                 )
             },
-            |num| (num >= block_number).then_some(num),
+            move |num| (num.saturating_sub(confirmations) >= block_number).then_some(num),
         )
         .await;
+        crate::metrics::observe_head(self.chain, head);
         trace!(block = block_number, "waited for block");
     }
 
+    /// Records `block_number`'s hash and, if its parent hash doesn't match
+    /// what was last seen at the previous height, returns the height to
+    /// rescan from. Guards against reorgs that a provider doesn't flag via
+    /// `removed: true` on the logs it re-serves.
+    async fn check_for_reorg(&self, block_number: u64) -> Option<u64> {
+        let block = retry(|| async {
+            self.provider
+                .get_block(block_number)
+                .await
+                .map_err(Error::RpcProvider)
+        })
+        .await;
+        let (hash, parent_hash) = match block {
+            Some(block) => match block.hash {
+                Some(hash) => (hash, block.parent_hash),
+                None => return None,
+            },
+            None => return None,
+        };
+
+        let mut hashes = self.parent_hashes.lock().await;
+        let diverged = block_number > 0
+            && hashes
+                .hash_at(block_number - 1)
+                .is_some_and(|expected| expected != parent_hash);
+        if !diverged {
+            hashes.record(block_number, hash);
+            return None;
+        }
+
+        let mut rescan_from = block_number - 1;
+        while rescan_from > 0 && hashes.hash_at(rescan_from - 1).is_some() {
+            let ancestor_hash = retry(|| async {
+                self.provider
+                    .get_block(rescan_from - 1)
+                    .await
+                    .map_err(Error::RpcProvider)
+            })
+            .await
+            .and_then(|b| b.hash);
+            if ancestor_hash == hashes.hash_at(rescan_from - 1) {
+                break;
+            }
+            rescan_from -= 1;
+        }
+        hashes.forget_from(rescan_from);
+        Some(rescan_from)
+    }
+
     async fn get_block_events(&self, block_number: u64, addr: Address) -> SmallVec<[Event; 4]> {
         retry(move || {
             let provider = self.provider.clone();
             let filter = Filter::new()
                 .select(block_number)
                 .address(ValueOrArray::Value(addr));
-            async move { provider.get_logs(&filter).await }
+            let chain = self.chain;
+            async move {
+                provider.get_logs(&filter).await.map_err(|e| {
+                    crate::metrics::record_rpc_error(chain);
+                    e
+                })
+            }
         })
         .map(futures::stream::iter)
         .flatten_stream()
@@ -181,15 +505,21 @@ impl<M: Middleware> SsssPermitter<M> {
     }
 
     async fn decode_permitter_event(&self, log: Log) -> Option<Event> {
-        let (block, tx, log_index) = match (
-            log.block_number,
-            log.transaction_hash,
-            log.log_index,
-            log.removed,
-        ) {
-            (Some(block), Some(tx), Some(index), None) => (block.as_u64(), tx, index.as_u64()),
+        let (block, tx, log_index) = match (log.block_number, log.transaction_hash, log.log_index)
+        {
+            (Some(block), Some(tx), Some(index)) => (block.as_u64(), tx, index.as_u64()),
             _ => return None,
         };
+        let index = EventIndex { block, log_index };
+        if log.removed == Some(true) {
+            // The chain reorged this log away; the caller is responsible for
+            // undoing whatever state was derived from it at `index`.
+            return Some(Event {
+                kind: EventKind::Reverted { index },
+                tx: Some(tx),
+                index,
+            });
+        }
         let raw_log = (log.topics, log.data.to_vec()).into();
         let event = match SsssPermitterContractEvents::decode_log(&raw_log) {
             Ok(event) => event,
@@ -202,6 +532,7 @@ impl<M: Middleware> SsssPermitter<M> {
             retry_if(|| self.provider.get_transaction(tx), |tx| tx).await;
         let kind = match event {
             SsssPermitterContractEvents::ConfigurationFilter(_) => {
+                crate::metrics::record_configuration_event(self.chain);
                 let (identity, config): (H256, Vec<u8>) = AbiDecode::decode(input).unwrap();
                 EventKind::Configuration(ConfigurationEvent {
                     identity: identity.into(),
@@ -213,57 +544,403 @@ impl<M: Middleware> SsssPermitter<M> {
         Some(Event {
             kind,
             tx: Some(tx),
-            index: EventIndex { block, log_index },
+            index,
+        })
+    }
+}
+
+/// One item off the merged `newHeads`/log subscriptions that
+/// [`SsssPermitter::events_subscribed`] drives.
+enum Subscribed {
+    Log(Log),
+    Block(BlockStep),
+}
+
+impl SsssPermitter<WsProvider> {
+    /// Like [`SsssPermitter::events`], but delivered push-style from
+    /// `start_block` onward: the `newHeads` subscription is opened first, so
+    /// no block mined while we're still catching up can slip through the
+    /// gap, then everything from `start_block` up to the block observed at
+    /// subscribe time is backfilled with the same `eth_getLogs` polling
+    /// [`SsssPermitter::events`] uses — otherwise a chain resumed (or
+    /// started fresh) over `ws`/`wss` would silently skip every
+    /// `Configuration`/permit-request log older than whatever block height
+    /// the subscription happened to start at. Only sound for providers that
+    /// speak `ws`/`wss`, hence the dedicated impl.
+    pub fn events_subscribed(
+        &self,
+        start_block: u64,
+        stop_block: Option<u64>,
+    ) -> impl Stream<Item = BoxFuture<SmallVec<[Event; 4]>>> + '_ {
+        async_stream::stream!({
+            let heads = retry(|| async {
+                self.provider
+                    .subscribe_blocks()
+                    .await
+                    .map_err(Error::RpcProvider)
+            })
+            .await;
+            let logs = self.logs_subscribed(self.address).await.map(Subscribed::Log);
+
+            let subscribed_from = retry(|| async {
+                Ok::<_, Error<WsProvider>>(
+                    self.provider
+                        .get_block_number()
+                        .await
+                        .map_err(|e| {
+                            crate::metrics::record_rpc_error(self.chain);
+                            Error::RpcProvider(e)
+                        })?
+                        .as_u64(),
+                )
+            })
+            .await;
+            crate::metrics::observe_head(self.chain, subscribed_from);
+
+            if start_block <= subscribed_from {
+                trace!(
+                    from = start_block,
+                    to = subscribed_from,
+                    "backfilling before handing off to the log subscription"
+                );
+                for await item in self.events(start_block, Some(subscribed_from)) {
+                    yield item;
+                }
+            }
+
+            let blocks = self
+                .blocks_from_heads(heads, subscribed_from + 1)
+                .map(Subscribed::Block);
+            let mut merged = futures::stream::select(Box::pin(logs), Box::pin(blocks));
+            while let Some(item) = merged.next().await {
+                match item {
+                    Subscribed::Log(log) => {
+                        yield self
+                            .decode_permitter_event(log)
+                            .map(|event| event.into_iter().collect())
+                            .boxed();
+                    }
+                    Subscribed::Block(BlockStep::Block(block)) => {
+                        yield futures::future::ready(smallvec![Event {
+                            kind: EventKind::ProcessedBlock,
+                            index: EventIndex { block, log_index: 0 },
+                            tx: Default::default(),
+                        }]).boxed();
+                        if Some(block) == stop_block {
+                            break;
+                        }
+                    }
+                    Subscribed::Block(BlockStep::Reorg(rescan_from)) => {
+                        yield Self::reverted_from(rescan_from);
+                    }
+                }
+            }
         })
     }
+
+    /// Turns an already-open `newHeads` subscription into confirmed block
+    /// heights starting at `start_block`, running each one through
+    /// [`SsssPermitter::check_for_reorg`] just like the HTTP-polled
+    /// [`SsssPermitter::blocks`] does — a `ws`/`wss` chain gets the same
+    /// reorg detection and `confirmations` depth, not just a faster block
+    /// source. Taking the subscription (rather than opening it here) lets
+    /// [`Self::events_subscribed`] start it before backfilling, so no block
+    /// mined during the backfill is missed.
+    fn blocks_from_heads(
+        &self,
+        heads: impl Stream<Item = Block<TxHash>> + '_,
+        start_block: u64,
+    ) -> impl Stream<Item = BlockStep> + '_ {
+        async_stream::stream!({
+            let mut next_block = start_block;
+            for await head in heads {
+                let head_number = head.number.unwrap_or_default().as_u64();
+                crate::metrics::observe_head(self.chain, head_number);
+                let confirmed = head_number.saturating_sub(self.confirmations);
+                while next_block <= confirmed {
+                    if let Some(rescan_from) = self.check_for_reorg(next_block).await {
+                        warn!(
+                            from = rescan_from,
+                            to = next_block,
+                            "reorg detected; rescanning from divergence point"
+                        );
+                        yield BlockStep::Reorg(rescan_from);
+                        next_block = rescan_from;
+                        continue;
+                    }
+                    yield BlockStep::Block(next_block);
+                    next_block += 1;
+                }
+            }
+        })
+    }
+
+    /// Subscribes to `Configuration`/permit logs at `addr` over
+    /// `eth_subscribe("logs")`, pushed as they're mined rather than
+    /// re-queried per block.
+    async fn logs_subscribed(&self, addr: Address) -> impl Stream<Item = Log> + '_ {
+        let filter = Filter::new().address(ValueOrArray::Value(addr));
+        retry(|| async {
+            self.provider
+                .subscribe_logs(&filter)
+                .await
+                .map_err(Error::RpcProvider)
+        })
+        .await
+    }
 }
 
 pub async fn providers(
-    rpcs: impl Iterator<Item = impl AsRef<str>>,
+    endpoints: impl Iterator<Item = EndpointConfig>,
+    config: &ProvidersConfig,
+) -> Result<Providers, Error<Provider>> {
+    let (ws_endpoints, http_endpoints): (Vec<_>, Vec<_>) = endpoints.partition(|e| {
+        url::Url::parse(&e.rpc)
+            .map(|u| matches!(u.scheme(), "ws" | "wss"))
+            .unwrap_or(false)
+    });
+
+    let mut providers = http_providers(http_endpoints.into_iter(), config.quorum).await?;
+    providers.extend(ws_providers(ws_endpoints.into_iter().map(|e| e.rpc)).await?);
+    Ok(providers)
+}
+
+async fn http_providers(
+    endpoints: impl Iterator<Item = EndpointConfig>,
+    quorum: QuorumPolicy,
 ) -> Result<Providers, Error<Provider>> {
-    Ok(futures::stream::iter(rpcs.map(|rpc| {
-        let rpc = rpc.as_ref();
-        let url = url::Url::parse(rpc).map_err(|_| Error::UnsupportedRpc(rpc.into()))?;
+    Ok(futures::stream::iter(endpoints.enumerate().map(|(order, endpoint)| {
+        let url =
+            url::Url::parse(&endpoint.rpc).map_err(|_| Error::UnsupportedRpc(endpoint.rpc.clone()))?;
         if url.scheme() != "http" {
-            return Err(Error::UnsupportedRpc(rpc.into()));
+            return Err(Error::UnsupportedRpc(endpoint.rpc));
         }
-        Ok(RetryClient::new(
+        let client = RetryClient::new(
             Http::new(url),
             Box::<HttpRateLimitRetryPolicy>::default(),
-            10,
-            2_000,
-        ))
+            endpoint.retry.max_retries,
+            endpoint.retry.initial_backoff_ms,
+        );
+        Ok((order, endpoint.weight, endpoint.primary, client))
     }))
-    .map_ok(|provider| async move {
-        let chain_id = provider
+    .map_ok(|(order, weight, primary, client)| async move {
+        let chain_id = client
             .request::<[(); 0], U64>("eth_chainId", [])
             .await
             .map_err(ethers::providers::ProviderError::from)?
             .as_u64();
-        Ok((chain_id, provider))
+        Ok((chain_id, order, weight, primary, client))
     })
     .try_buffer_unordered(10)
     .try_fold(
         HashMap::<ChainId, Vec<_>>::new(),
-        |mut providers, (chain_id, provider)| async move {
-            providers.entry(chain_id).or_default().push(provider);
-            Ok(providers)
+        |mut groups, (chain_id, order, weight, primary, client)| async move {
+            groups
+                .entry(chain_id)
+                .or_default()
+                .push((order, weight, primary, client));
+            Ok(groups)
         },
     )
     .await?
     .into_iter()
-    .map(|(chain_id, providers)| {
-        (
-            chain_id,
-            EthersProvider::new(Arc::new(QuorumProvider::new(
-                Quorum::Majority,
-                providers.into_iter().map(WeightedProvider::new),
-            ))),
-        )
-    })
+    .map(|(chain_id, members)| (chain_id, AnyProvider::Http(quorum_provider(members, quorum))))
     .collect())
 }
 
+/// A [`JsonRpcClient`] that tries its `primary` endpoint first and only
+/// spends a request on the `fallback` pool once the primary errors, unlike
+/// `QuorumProvider`'s fan-out-to-everyone semantics. Used for a chain with
+/// an [`EndpointConfig::as_primary`] endpoint, so the primary's "several
+/// cheap public fallbacks" are only ever queried when it's down.
+#[derive(Debug)]
+struct PrimaryFallbackClient {
+    primary: RetryClient<Http>,
+    fallback: QuorumProvider<RetryClient<Http>>,
+}
+
+#[derive(Debug, thiserror::Error)]
+enum PrimaryFallbackError {
+    #[error("failed to serialize request params: {0}")]
+    Serialize(#[from] serde_json::Error),
+    #[error("fallback providers also failed: {0}")]
+    Fallback(<QuorumProvider<RetryClient<Http>> as ethers::providers::JsonRpcClient>::Error),
+}
+
+impl From<PrimaryFallbackError> for ProviderError {
+    fn from(e: PrimaryFallbackError) -> Self {
+        match e {
+            PrimaryFallbackError::Serialize(e) => ProviderError::SerdeJson(e),
+            PrimaryFallbackError::Fallback(e) => e.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl ethers::providers::JsonRpcClient for PrimaryFallbackClient {
+    type Error = PrimaryFallbackError;
+
+    async fn request<T, R>(&self, method: &str, params: T) -> Result<R, Self::Error>
+    where
+        T: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        // Serialized once so the same params can be replayed against the
+        // fallback pool without requiring `T: Clone`.
+        let params = serde_json::to_value(params)?;
+        match self.primary.request(method, &params).await {
+            Ok(r) => Ok(r),
+            Err(e) => {
+                warn!("primary rpc endpoint failed ({e}); falling back");
+                self.fallback
+                    .request(method, &params)
+                    .await
+                    .map_err(PrimaryFallbackError::Fallback)
+            }
+        }
+    }
+}
+
+/// The underlying JSON-RPC transport for one chain's HTTP [`Provider`]:
+/// either a plain `QuorumProvider` over its endpoints, or — once a chain has
+/// an [`EndpointConfig::as_primary`] endpoint — a [`PrimaryFallbackClient`].
+#[derive(Debug)]
+pub enum HttpClient {
+    Quorum(QuorumProvider<RetryClient<Http>>),
+    PrimaryFallback(PrimaryFallbackClient),
+}
+
+#[async_trait]
+impl ethers::providers::JsonRpcClient for HttpClient {
+    type Error = ProviderError;
+
+    async fn request<T, R>(&self, method: &str, params: T) -> Result<R, Self::Error>
+    where
+        T: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        match self {
+            HttpClient::Quorum(c) => c.request(method, params).await.map_err(Into::into),
+            HttpClient::PrimaryFallback(c) => c.request(method, params).await.map_err(Into::into),
+        }
+    }
+}
+
+/// Builds one chain's HTTP transport from its endpoints. A chain with a
+/// primary endpoint (the one with the lowest `order`, i.e. listed first in
+/// configuration — see the `primary_count > 1` warning below) gets a
+/// [`PrimaryFallbackClient`] instead of a `QuorumProvider`: every request
+/// goes to the primary alone, and the rest of the chain's endpoints — still
+/// combined under the configured [`QuorumPolicy`] — are only queried once
+/// the primary errors. A chain with no primary endpoint keeps the previous
+/// plain `QuorumProvider` behavior.
+fn quorum_provider(members: Vec<(usize, u64, bool, RetryClient<Http>)>, quorum: QuorumPolicy) -> Provider {
+    let primary_count = members.iter().filter(|(_, _, primary, _)| *primary).count();
+    // `members`' order reflects `try_buffer_unordered`'s completion order,
+    // not configuration order, so the "first" primary is picked by `order`
+    // (captured before that fan-out) rather than by position in this `Vec`.
+    let primary_order = members
+        .iter()
+        .filter(|(_, _, primary, _)| *primary)
+        .map(|(order, ..)| *order)
+        .min();
+    if primary_count > 1 {
+        warn!(
+            "{primary_count} endpoints marked primary for one chain; the one listed \
+             first in configuration is used, the rest are treated as fallbacks"
+        );
+    }
+
+    let Some(primary_order) = primary_order else {
+        let weighted = members
+            .into_iter()
+            .map(|(_, weight, _, client)| WeightedProvider::with_weight(client, weight));
+        return EthersProvider::new(Arc::new(HttpClient::Quorum(QuorumProvider::new(
+            quorum.into(),
+            weighted,
+        ))));
+    };
+
+    let mut primary = None;
+    let mut fallbacks = Vec::with_capacity(members.len().saturating_sub(1));
+    for (order, weight, _, client) in members {
+        if order == primary_order {
+            primary = Some(client);
+        } else {
+            fallbacks.push(WeightedProvider::with_weight(client, weight));
+        }
+    }
+    let fallback = QuorumProvider::new(quorum.into(), fallbacks.into_iter());
+    EthersProvider::new(Arc::new(HttpClient::PrimaryFallback(PrimaryFallbackClient {
+        primary: primary.expect("primary_order came from a member of `members`"),
+        fallback,
+    })))
+}
+
+#[cfg(test)]
+mod quorum_provider_tests {
+    use super::*;
+
+    fn member(order: usize, weight: u64, primary: bool) -> (usize, u64, bool, RetryClient<Http>) {
+        let client = RetryClient::new(
+            Http::new(url::Url::parse("http://localhost:0").unwrap()),
+            Box::<HttpRateLimitRetryPolicy>::default(),
+            0,
+            0,
+        );
+        (order, weight, primary, client)
+    }
+
+    #[test]
+    fn first_configured_primary_wins_regardless_of_vec_order() {
+        // Simulates `try_buffer_unordered` resolving endpoint #2 before #1.
+        let members = vec![member(2, 1, true), member(1, 1, true), member(0, 1, false)];
+        let primary_order = members
+            .iter()
+            .filter(|(_, _, primary, _)| *primary)
+            .map(|(order, ..)| *order)
+            .min();
+        assert_eq!(primary_order, Some(1));
+    }
+
+    #[test]
+    fn no_primary_marked_yields_none() {
+        let members = vec![member(0, 1, false), member(1, 1, false)];
+        let primary_order = members
+            .iter()
+            .filter(|(_, _, primary, _)| *primary)
+            .map(|(order, ..)| *order)
+            .min();
+        assert_eq!(primary_order, None);
+    }
+}
+
+async fn ws_providers(
+    rpcs: impl Iterator<Item = impl AsRef<str>>,
+) -> Result<Providers, Error<Provider>> {
+    futures::stream::iter(rpcs.map(|rpc| {
+        let rpc = rpc.as_ref().to_string();
+        async move {
+            let url = url::Url::parse(&rpc).map_err(|_| Error::UnsupportedRpc(rpc.clone()))?;
+            if !matches!(url.scheme(), "ws" | "wss") {
+                return Err(Error::UnsupportedRpc(rpc));
+            }
+            let ws = Ws::connect(url)
+                .await
+                .map_err(|e| Error::Connection(rpc.clone(), e.to_string()))?;
+            let chain_id = ws
+                .request::<[(); 0], U64>("eth_chainId", [])
+                .await
+                .map_err(ethers::providers::ProviderError::from)?
+                .as_u64();
+            Ok((chain_id, AnyProvider::Ws(EthersProvider::new(ws))))
+        }
+    }))
+    .buffer_unordered(10)
+    .try_collect()
+    .await
+}
+
 #[derive(Clone, Debug)]
 pub struct Event {
     pub kind: EventKind,
@@ -274,6 +951,10 @@ pub struct Event {
 #[derive(Clone, Debug)]
 pub enum EventKind {
     Configuration(ConfigurationEvent),
+    PermitRequest(PermitRequestEvent),
+    /// A previously-delivered log at `index` was reorged away; whatever
+    /// state was derived from it should be undone.
+    Reverted { index: EventIndex },
     ProcessedBlock,
 }
 
@@ -283,6 +964,30 @@ pub struct ConfigurationEvent {
     pub config: Vec<u8>,
 }
 
+#[derive(Clone, Debug)]
+pub struct PermitRequestEvent {
+    pub identity: IdentityId,
+    pub requester: Address,
+    pub kind: PermitRequestKind,
+}
+
+impl PermitRequestEvent {
+    /// The attestation scheme (e.g. `"nitro"`) this request's context was
+    /// produced by, used to pick a [`Verifier`](crate::verify::Verifier).
+    pub fn selector(&self) -> Option<&str> {
+        match &self.kind {
+            PermitRequestKind::Nitro(_) => Some("nitro"),
+            PermitRequestKind::Unknown(_) => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum PermitRequestKind {
+    Nitro(Vec<u8>),
+    Unknown(Vec<u8>),
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum Error<M: Middleware> {
     #[error("contract call error: {0}")]
@@ -293,4 +998,6 @@ pub enum Error<M: Middleware> {
     Provider(#[from] ethers::providers::ProviderError),
     #[error("unsupported rpc url: {0}")]
     UnsupportedRpc(String),
+    #[error("failed to connect to {0}: {1}")]
+    Connection(String, String),
 }
\ No newline at end of file
@@ -0,0 +1,146 @@
+//! Prometheus/OpenMetrics counters and gauges for per-chain sync health,
+//! exposed over a small HTTP endpoint (as Garage's admin server does) when
+//! the `metrics` feature is enabled. With the feature off, every function
+//! here is a no-op so call sites don't need to sprinkle `#[cfg(...)]`.
+
+#[cfg(feature = "metrics")]
+mod imp {
+    use std::net::SocketAddr;
+
+    use once_cell::sync::Lazy;
+    use prometheus::{
+        register_int_counter_vec, register_int_gauge_vec, Encoder, IntCounterVec, IntGaugeVec,
+        TextEncoder,
+    };
+
+    static HEAD_BLOCK: Lazy<IntGaugeVec> = Lazy::new(|| {
+        register_int_gauge_vec!(
+            "escrin_ssss_chain_head_block",
+            "Most recent block number observed on the chain",
+            &["chain"]
+        )
+        .unwrap()
+    });
+
+    static PROCESSED_BLOCK: Lazy<IntGaugeVec> = Lazy::new(|| {
+        register_int_gauge_vec!(
+            "escrin_ssss_chain_processed_block",
+            "Most recent block number whose events have been applied",
+            &["chain"]
+        )
+        .unwrap()
+    });
+
+    static SYNC_LAG: Lazy<IntGaugeVec> = Lazy::new(|| {
+        register_int_gauge_vec!(
+            "escrin_ssss_chain_sync_lag_blocks",
+            "Blocks between the chain head and the last processed block",
+            &["chain"]
+        )
+        .unwrap()
+    });
+
+    static CONFIGURATION_EVENTS: Lazy<IntCounterVec> = Lazy::new(|| {
+        register_int_counter_vec!(
+            "escrin_ssss_configuration_events_total",
+            "Decoded Configuration events",
+            &["chain"]
+        )
+        .unwrap()
+    });
+
+    static PERMIT_EVENTS: Lazy<IntCounterVec> = Lazy::new(|| {
+        register_int_counter_vec!(
+            "escrin_ssss_permit_events_total",
+            "Decoded permit request events",
+            &["chain"]
+        )
+        .unwrap()
+    });
+
+    static RPC_ERRORS: Lazy<IntCounterVec> = Lazy::new(|| {
+        register_int_counter_vec!(
+            "escrin_ssss_rpc_errors_total",
+            "RPC calls that failed, whether or not a retry later succeeded",
+            &["chain"]
+        )
+        .unwrap()
+    });
+
+    static VERIFICATIONS: Lazy<IntCounterVec> = Lazy::new(|| {
+        register_int_counter_vec!(
+            "escrin_ssss_verifications_total",
+            "Verifier outcomes for permit requests",
+            &["chain", "result"]
+        )
+        .unwrap()
+    });
+
+    pub fn observe_head(chain: u64, head_block: u64) {
+        HEAD_BLOCK
+            .with_label_values(&[&chain.to_string()])
+            .set(head_block as i64);
+    }
+
+    pub fn observe_processed(chain: u64, processed_block: u64) {
+        let chain = chain.to_string();
+        PROCESSED_BLOCK
+            .with_label_values(&[&chain])
+            .set(processed_block as i64);
+        let lag = HEAD_BLOCK.with_label_values(&[&chain]).get() - processed_block as i64;
+        SYNC_LAG.with_label_values(&[&chain]).set(lag.max(0));
+    }
+
+    pub fn record_configuration_event(chain: u64) {
+        CONFIGURATION_EVENTS
+            .with_label_values(&[&chain.to_string()])
+            .inc();
+    }
+
+    pub fn record_permit_event(chain: u64) {
+        PERMIT_EVENTS.with_label_values(&[&chain.to_string()]).inc();
+    }
+
+    pub fn record_rpc_error(chain: u64) {
+        RPC_ERRORS.with_label_values(&[&chain.to_string()]).inc();
+    }
+
+    pub fn record_verification(chain: u64, pass: bool) {
+        let result = if pass { "pass" } else { "fail" };
+        VERIFICATIONS
+            .with_label_values(&[&chain.to_string(), result])
+            .inc();
+    }
+
+    /// Serves the registered metrics in OpenMetrics/Prometheus text format at
+    /// `GET /metrics` until the process exits.
+    pub async fn serve(addr: SocketAddr) {
+        use warp::Filter as _;
+
+        let metrics = warp::path("metrics").map(|| {
+            let families = prometheus::gather();
+            let mut buf = Vec::new();
+            TextEncoder::new()
+                .encode(&families, &mut buf)
+                .expect("encoding the metric families should never fail");
+            warp::http::Response::builder()
+                .header("content-type", TextEncoder::new().format_type())
+                .body(buf)
+        });
+
+        warp::serve(metrics).run(addr).await;
+    }
+}
+
+#[cfg(not(feature = "metrics"))]
+mod imp {
+    pub fn observe_head(_chain: u64, _head_block: u64) {}
+    pub fn observe_processed(_chain: u64, _processed_block: u64) {}
+    pub fn record_configuration_event(_chain: u64) {}
+    pub fn record_permit_event(_chain: u64) {}
+    pub fn record_rpc_error(_chain: u64) {}
+    pub fn record_verification(_chain: u64, _pass: bool) {}
+    pub async fn serve(_addr: std::net::SocketAddr) {}
+}
+
+pub use imp::*;
@@ -1,54 +1,97 @@
 mod eth;
 
-use std::sync::{
-    atomic::{AtomicU64, Ordering},
-    Arc,
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
 };
 
-use ethers::types::Address;
+use ethers::{providers::Middleware, types::Address};
 use futures::stream::StreamExt as _;
-use tokio::time::{sleep, Duration};
+use tokio::{
+    sync::Mutex,
+    time::{sleep, Duration},
+};
 use tracing::{debug, error, trace, warn};
 
 pub use self::eth::{PermitRequestEvent, PermitRequestKind};
 use crate::{
     store::Store,
-    types::{ChainState, ChainStateUpdate, PermitterLocator},
+    types::{ChainState, ChainStateUpdate, IdentityId, PermitterLocator},
     utils::{retry, retry_times},
     verify::Verifier as _,
 };
 
+/// How many verified permit requests to batch into one `approveRequests`
+/// call before flushing early, independent of the time-based flush below.
+const PERMIT_BATCH_SIZE: usize = 16;
+/// How long a verified-but-unflushed permit request may sit buffered before
+/// it's sent on its own, so a quiet chain doesn't leave requesters waiting
+/// for a batch that will never fill up.
+const PERMIT_BATCH_INTERVAL: Duration = Duration::from_secs(30);
+
+type PendingPermits = Arc<Mutex<Vec<(IdentityId, Address, u64)>>>;
+
 #[tracing::instrument(skip_all)]
 pub async fn run(
     store: impl Store + 'static,
-    gateways: impl Iterator<Item = impl AsRef<str>>,
+    endpoints: impl Iterator<Item = eth::EndpointConfig>,
     permitter_addr: Address,
+    confirmations: &HashMap<eth::ChainId, u64>,
+    providers_config: &eth::ProvidersConfig,
+    metrics_addr: Option<std::net::SocketAddr>,
 ) -> Result<(), eth::Error> {
+    if let Some(addr) = metrics_addr {
+        trace!("starting metrics endpoint on {addr}");
+        tokio::spawn(crate::metrics::serve(addr));
+    }
+
     trace!("collating providers");
-    let providers = eth::providers(gateways).await?;
+    let providers = eth::providers(endpoints, providers_config).await?;
 
     for (chain, provider) in providers.into_iter() {
         let store = store.clone();
-        let permitter = eth::SsssPermitter::new(chain, permitter_addr, provider);
+        let confirmations = confirmations.get(&chain).copied().unwrap_or(0);
         trace!("launching task for chain {chain}");
-        tokio::spawn(async move {
-            loop {
-                match sync_chain(chain, &permitter, &store).await {
-                    Ok(_) => warn!("sync task for chain {chain} unexpectedly exited"),
-                    Err(e) => error!("sync task for chain {chain} exited with error: {e}"),
-                }
-                sleep(Duration::from_millis(1000)).await;
+        match provider {
+            eth::AnyProvider::Http(provider) => {
+                let permitter =
+                    eth::SsssPermitter::with_confirmations(chain, permitter_addr, provider, confirmations);
+                tokio::spawn(async move {
+                    loop {
+                        match sync_chain(chain, &permitter, &store).await {
+                            Ok(_) => warn!("sync task for chain {chain} unexpectedly exited"),
+                            Err(e) => error!("sync task for chain {chain} exited with error: {e}"),
+                        }
+                        sleep(Duration::from_millis(1000)).await;
+                    }
+                });
             }
-        });
+            eth::AnyProvider::Ws(provider) => {
+                let permitter =
+                    eth::SsssPermitter::with_confirmations(chain, permitter_addr, provider, confirmations);
+                tokio::spawn(async move {
+                    loop {
+                        match sync_chain_subscribed(chain, &permitter, &store).await {
+                            Ok(_) => warn!("sync task for chain {chain} unexpectedly exited"),
+                            Err(e) => error!("sync task for chain {chain} exited with error: {e}"),
+                        }
+                        sleep(Duration::from_millis(1000)).await;
+                    }
+                });
+            }
+        }
     }
 
     Ok(())
 }
 
 #[tracing::instrument(skip_all)]
-async fn sync_chain<S: Store + 'static>(
+async fn sync_chain<M: Middleware + 'static, S: Store + 'static>(
     chain_id: eth::ChainId,
-    permitter: &eth::SsssPermitter,
+    permitter: &eth::SsssPermitter<M>,
     store: &S,
 ) -> Result<(), Error> {
     let start_block = match store.get_chain_state(chain_id).await? {
@@ -57,37 +100,147 @@ async fn sync_chain<S: Store + 'static>(
     };
 
     let processed_block = Arc::new(AtomicU64::new(start_block));
-    let state_updater_task = tokio::spawn({
-        let store = store.clone();
-        let processed_block = processed_block.clone();
-        async move {
-            loop {
-                sleep(Duration::from_secs(5 * 60)).await;
-                debug!("updating sync state for chain {chain_id}");
-                if let Err(e) = store
-                    .update_chain_state(
-                        chain_id,
-                        ChainStateUpdate {
-                            block: Some(processed_block.load(Ordering::Acquire)),
-                        },
-                    )
-                    .await
-                {
-                    warn!("failed to update sync state for chain {chain_id}: {e}");
-                }
+    let state_updater_task = spawn_state_updater(chain_id, store.clone(), processed_block.clone());
+    let pending_permits = PendingPermits::default();
+    let permit_flusher_task = spawn_permit_flusher(permitter.clone(), pending_permits.clone());
+
+    drive_events(
+        chain_id,
+        permitter.events(start_block, None),
+        store,
+        permitter,
+        &processed_block,
+        &pending_permits,
+    )
+    .await;
+
+    permit_flusher_task.abort();
+    flush_pending_permits(permitter, &pending_permits).await;
+    state_updater_task.abort();
+    Ok(())
+}
+
+/// Like [`sync_chain`], but for `ws`/`wss` providers: events are pushed over
+/// a subscription rather than polled, backfilling from `start_block` before
+/// the subscription takes over (see [`eth::SsssPermitter::events_subscribed`]).
+#[tracing::instrument(skip_all)]
+async fn sync_chain_subscribed<S: Store + 'static>(
+    chain_id: eth::ChainId,
+    permitter: &eth::SsssPermitter<eth::WsProvider>,
+    store: &S,
+) -> Result<(), Error> {
+    let start_block = match store.get_chain_state(chain_id).await? {
+        Some(ChainState { block }) => block,
+        None => permitter.creation_block().await?,
+    };
+
+    let processed_block = Arc::new(AtomicU64::new(start_block));
+    let state_updater_task = spawn_state_updater(chain_id, store.clone(), processed_block.clone());
+    let pending_permits = PendingPermits::default();
+    let permit_flusher_task = spawn_permit_flusher(permitter.clone(), pending_permits.clone());
+
+    drive_events(
+        chain_id,
+        permitter.events_subscribed(start_block, None),
+        store,
+        permitter,
+        &processed_block,
+        &pending_permits,
+    )
+    .await;
+
+    permit_flusher_task.abort();
+    flush_pending_permits(permitter, &pending_permits).await;
+    state_updater_task.abort();
+    Ok(())
+}
+
+fn spawn_state_updater<S: Store + 'static>(
+    chain_id: eth::ChainId,
+    store: S,
+    processed_block: Arc<AtomicU64>,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            sleep(Duration::from_secs(5 * 60)).await;
+            debug!("updating sync state for chain {chain_id}");
+            if let Err(e) = store
+                .update_chain_state(
+                    chain_id,
+                    ChainStateUpdate {
+                        block: Some(processed_block.load(Ordering::Acquire)),
+                    },
+                )
+                .await
+            {
+                warn!("failed to update sync state for chain {chain_id}: {e}");
             }
         }
-    });
+    })
+}
+
+fn spawn_permit_flusher<M: Middleware + 'static>(
+    permitter: eth::SsssPermitter<M>,
+    pending: PendingPermits,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            sleep(PERMIT_BATCH_INTERVAL).await;
+            flush_pending_permits(&permitter, &pending).await;
+        }
+    })
+}
+
+/// Sends every buffered `(identity, requester, duration)` tuple as a single
+/// `approveRequests` transaction. On failure the whole batch is re-queued so
+/// the next flush (size- or time-triggered) retries it.
+async fn flush_pending_permits<M: Middleware>(permitter: &eth::SsssPermitter<M>, pending: &PendingPermits) {
+    let batch = {
+        let mut pending = pending.lock().await;
+        if pending.is_empty() {
+            return;
+        }
+        std::mem::take(&mut *pending)
+    };
+
+    let mut identities = Vec::with_capacity(batch.len());
+    let mut requesters = Vec::with_capacity(batch.len());
+    let mut durations = Vec::with_capacity(batch.len());
+    for (identity, requester, duration) in batch.iter().copied() {
+        identities.push(identity);
+        requesters.push(requester);
+        durations.push(duration);
+    }
 
-    let processed_block = &processed_block;
-    permitter
-        .events(start_block, None)
+    let len = batch.len();
+    match permitter
+        .approve_requests(identities, requesters, durations)
+        .await
+    {
+        Ok(tx) => debug!("approved {len} permit request(s) in {tx}"),
+        Err(e) => {
+            error!("failed to approve {len} permit request(s): {e}; re-queuing");
+            pending.lock().await.extend(batch);
+        }
+    }
+}
+
+async fn drive_events<M: Middleware, S: Store + 'static>(
+    chain_id: eth::ChainId,
+    events: impl futures::Stream<Item = futures::future::BoxFuture<smallvec::SmallVec<[eth::Event; 4]>>>,
+    store: &S,
+    permitter: &eth::SsssPermitter<M>,
+    processed_block: &AtomicU64,
+    pending_permits: &PendingPermits,
+) {
+    events
         .buffered(100)
         .map(futures::stream::iter)
         .flatten()
         .for_each(|event| async move {
             match event.kind {
                 eth::EventKind::PermitRequest(req) => {
+                    crate::metrics::record_permit_event(chain_id);
                     let policy_result = retry_times(
                         || {
                             store.get_verifier(
@@ -106,6 +259,9 @@ async fn sync_chain<S: Store + 'static>(
                             return;
                         }
                     };
+                    // `Verifier::verify` returns the duration (in seconds) the
+                    // requester should be granted, or `None` if verification
+                    // failed — see `crate::verify`.
                     let pass = match req.selector().as_deref() {
                         #[cfg(feature = "aws")]
                         Some("nitro") => {
@@ -121,10 +277,18 @@ async fn sync_chain<S: Store + 'static>(
                             None
                         }
                     };
-                    if pass.is_none() {
+                    crate::metrics::record_verification(chain_id, pass.is_some());
+                    let Some(duration) = pass else {
                         return;
+                    };
+                    let should_flush = {
+                        let mut pending = pending_permits.lock().await;
+                        pending.push((req.identity, req.requester, duration));
+                        pending.len() >= PERMIT_BATCH_SIZE
+                    };
+                    if should_flush {
+                        flush_pending_permits(permitter, pending_permits).await;
                     }
-                    todo!()
                 }
                 eth::EventKind::Configuration(eth::ConfigurationEvent { identity, config }) => {
                     retry(|| {
@@ -137,15 +301,19 @@ async fn sync_chain<S: Store + 'static>(
                     })
                     .await;
                 }
+                eth::EventKind::Reverted { index } => {
+                    retry(|| {
+                        store.revert_verifier(PermitterLocator::new(chain_id, permitter.address), index)
+                    })
+                    .await;
+                }
                 eth::EventKind::ProcessedBlock => {
                     processed_block.store(event.index.block, Ordering::Release);
+                    crate::metrics::observe_processed(chain_id, event.index.block);
                 }
             }
         })
         .await;
-
-    state_updater_task.abort();
-    Ok(())
 }
 
 #[derive(Debug, thiserror::Error)]